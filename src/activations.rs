@@ -0,0 +1,39 @@
+//! Elementwise nonlinearities for use with [`crate::Matrix::map`] after
+//! [`crate::Matrix::affine`], e.g.
+//! `m.affine(&w1, &b1).map(activations::f64::sigmoid)`.
+
+pub mod f32 {
+    pub fn sigmoid(x: f32) -> f32 {
+        1.0 / (1.0 + (-x).exp())
+    }
+
+    pub fn tanh(x: f32) -> f32 {
+        x.tanh()
+    }
+
+    pub fn relu(x: f32) -> f32 {
+        if x > 0.0 {
+            x
+        } else {
+            0.0
+        }
+    }
+}
+
+pub mod f64 {
+    pub fn sigmoid(x: f64) -> f64 {
+        1.0 / (1.0 + (-x).exp())
+    }
+
+    pub fn tanh(x: f64) -> f64 {
+        x.tanh()
+    }
+
+    pub fn relu(x: f64) -> f64 {
+        if x > 0.0 {
+            x
+        } else {
+            0.0
+        }
+    }
+}
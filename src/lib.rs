@@ -2,6 +2,11 @@ use colored::Colorize;
 use core::fmt;
 use std::ops::{Add, Sub, Mul};
 
+pub mod modint;
+pub use modint::ModInt;
+
+pub mod activations;
+
 #[derive(Clone)]
 pub struct Matrix<T> {
     pub rows: usize,
@@ -9,6 +14,37 @@ pub struct Matrix<T> {
     pub data: Vec<Vec<T>>,
 }
 
+/// Error returned by the fallible (`try_*`) side of the `Matrix<T>` API.
+///
+/// Unlike the panicking constructors/operators, these are meant to be used
+/// inside long-running services where a bad dimension shouldn't take the
+/// whole process down with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The two matrices involved do not share the same dimensions.
+    MismatchedDims,
+    /// The rows making up a matrix are not all the same length.
+    RaggedRows,
+    /// `lhs.cols != rhs.rows`, so `lhs.dot(rhs)` cannot be computed.
+    IncompatibleForDot,
+    /// A row or column index was out of bounds.
+    IndexOutOfBounds,
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            MatrixError::MismatchedDims => "Matrices must have same dims.",
+            MatrixError::RaggedRows => "All rows must have same length.",
+            MatrixError::IncompatibleForDot => "Incompatible matrices dims for dot product.",
+            MatrixError::IndexOutOfBounds => "Invalid row or column index requested.",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
 impl<T> Matrix<T>
 where
     T: Clone + Default + From<u8> + Mul + std::iter::Sum<<T as Mul>::Output>,
@@ -47,6 +83,100 @@ where
         }
     }
 
+    /// Non-panicking counterpart of [`Matrix::new`].
+    pub fn try_new(rows: Vec<Vec<T>>) -> Result<Self, MatrixError> {
+        let cols = match rows.first() {
+            Some(first) => first.len(),
+            None => 0,
+        };
+
+        if rows.iter().any(|r| r.len() != cols) {
+            return Err(MatrixError::RaggedRows);
+        }
+
+        Ok(Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows,
+        })
+    }
+
+    // Non-panicking counterpart of `check_valid_dims`.
+    fn try_check_valid_dims(&self) -> Result<(), MatrixError> {
+        let expected_cols = match self.data.first() {
+            Some(first) => first.len(),
+            None => 0,
+        };
+
+        if self.data.iter().any(|r| r.len() != expected_cols)
+            || self.data.len() != self.rows
+            || self.data.iter().any(|r| r.len() != self.cols)
+        {
+            return Err(MatrixError::RaggedRows);
+        }
+
+        Ok(())
+    }
+
+    // Non-panicking counterpart of `check_same_dims`.
+    fn try_check_same_dims(&self, rhs: &Self) -> Result<(), MatrixError> {
+        self.try_check_valid_dims()?;
+        rhs.try_check_valid_dims()?;
+
+        if self.rows != rhs.rows || self.cols != rhs.cols {
+            return Err(MatrixError::MismatchedDims);
+        }
+
+        Ok(())
+    }
+
+    /// Non-panicking counterpart of [`Matrix::row`].
+    pub fn try_row(&self, i: usize) -> Result<Vec<T>, MatrixError> {
+        if i >= self.rows {
+            return Err(MatrixError::IndexOutOfBounds);
+        }
+
+        Ok(self.data[i].clone())
+    }
+
+    /// Non-panicking counterpart of [`Matrix::col`].
+    pub fn try_col(&self, i: usize) -> Result<Vec<T>, MatrixError> {
+        if i >= self.cols {
+            return Err(MatrixError::IndexOutOfBounds);
+        }
+
+        Ok(self.data.iter().map(|r| r[i].clone()).collect())
+    }
+
+    /// Non-panicking counterpart of [`Matrix::dot`].
+    pub fn try_dot(&self, rhs: &Self) -> Result<Self, MatrixError> {
+        if self.cols != rhs.rows {
+            return Err(MatrixError::IncompatibleForDot);
+        }
+        self.try_check_valid_dims()?;
+        rhs.try_check_valid_dims()?;
+
+        // Built directly: `Matrix::ones` goes through `Matrix::new`, which
+        // panics when there are no rows.
+        let mut res: Matrix<T> = Matrix {
+            rows: self.rows,
+            cols: rhs.cols,
+            data: vec![vec![T::from(1u8); rhs.cols]; self.rows],
+        };
+
+        for n in 0..self.rows {
+            for p in 0..rhs.cols {
+                res.data[n][p] = self
+                    .try_row(n)?
+                    .iter()
+                    .zip(rhs.try_col(p)?)
+                    .map(|(a, b)| a.clone() * b.clone())
+                    .sum()
+            }
+        }
+        Ok(res)
+    }
+
     pub fn zeros(n: usize, p: usize) -> Self {
         Matrix::new(vec![vec![T::from(0u8); p]; n])
     }
@@ -55,6 +185,15 @@ where
         Matrix::new(vec![vec![T::from(1u8); p]; n])
     }
 
+    pub fn identity(n: usize) -> Self {
+        let mut data = vec![vec![T::from(0u8); n]; n];
+        for (i, row) in data.iter_mut().enumerate() {
+            row[i] = T::from(1u8);
+        }
+
+        Matrix::new(data)
+    }
+
     pub fn row(&self, i: usize) -> Vec<T> {
         if i >= self.rows {
             panic!("{}", "Invalid row index requested.".red());
@@ -91,6 +230,29 @@ where
         res
     }
 
+    // Square matrix exponentiation by repeated squaring, the standard tool
+    // for evaluating linear recurrences and reachability counts in
+    // O(rows^3 log exp) instead of O(rows^3 * exp).
+    pub fn pow(&self, exp: u64) -> Self {
+        if self.rows != self.cols {
+            panic!("{}", "Matrix must be square to raise it to a power.".red());
+        }
+
+        let mut result = Matrix::identity(self.rows);
+        let mut base = self.clone();
+        let mut exp = exp;
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.dot(&base);
+            }
+            base = base.dot(&base);
+            exp >>= 1;
+        }
+
+        result
+    }
+
     pub fn dot_scalar(&self, a: T) -> Self {
         let mut res: Matrix<T> = self.clone();
 
@@ -109,6 +271,17 @@ where
 
         Matrix::new(data)
     }
+
+    /// Applies `f` to every element, e.g. a nonlinearity after `affine`.
+    pub fn map<F: Fn(T) -> T>(&self, f: F) -> Self {
+        let data = self
+            .data
+            .iter()
+            .map(|row| row.iter().map(|v| f(v.clone())).collect())
+            .collect();
+
+        Matrix::new(data)
+    }
 }
 
 impl<T: std::ops::Add> Add for Matrix<T>
@@ -163,6 +336,431 @@ where
     }
 }
 
+impl<T> Matrix<T>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Clone
+        + Default
+        + From<u8>
+        + Mul
+        + std::iter::Sum<<T as Mul>::Output>,
+    Vec<T>: FromIterator<<T as Add>::Output>,
+    Vec<T>: FromIterator<<T as Sub>::Output>,
+    Vec<T>: FromIterator<<T as Mul>::Output>
+{
+    /// Non-panicking counterpart of the `Add` impl.
+    pub fn try_add(self, rhs: Self) -> Result<Self, MatrixError> {
+        self.try_check_same_dims(&rhs)?;
+
+        Matrix::try_new(
+            self.data
+                .into_iter()
+                .zip(rhs.data)
+                .map(|(a, b)| a.into_iter().zip(b).map(|(x, y)| x + y).collect::<Vec<T>>())
+                .collect::<Vec<Vec<T>>>(),
+        )
+    }
+
+    /// Non-panicking counterpart of the `Sub` impl.
+    pub fn try_sub(self, rhs: Self) -> Result<Self, MatrixError> {
+        self.try_check_same_dims(&rhs)?;
+
+        Matrix::try_new(
+            self.data
+                .into_iter()
+                .zip(rhs.data)
+                .map(|(a, b)| a.into_iter().zip(b).map(|(x, y)| x - y).collect::<Vec<T>>())
+                .collect::<Vec<Vec<T>>>(),
+        )
+    }
+}
+
+/// Multiplication algorithm for [`Matrix::dot_with`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DotStrategy {
+    /// [`Matrix::dot`]: one dot product per output cell, re-reading a
+    /// column of `rhs` for every cell.
+    Naive,
+    /// ikj-ordered accumulation: each element of `self` is read once and
+    /// scaled into the whole output row at once, so `rhs` is walked
+    /// row-by-row instead of having a column re-cloned per output cell.
+    Tiled,
+    /// Strassen's 7-multiply recurrence on square operands, falling back
+    /// to `Tiled` below [`STRASSEN_THRESHOLD`].
+    Strassen,
+}
+
+/// Matrix size below which [`Matrix::dot_strassen`] falls back to
+/// [`Matrix::dot_tiled`] instead of recursing further.
+pub const STRASSEN_THRESHOLD: usize = 64;
+
+// The ikj/Strassen rewrites accumulate into the output cell-by-cell, so
+// they need the `Add`/`Sub` bounds the plain integer API above does not.
+impl<T> Matrix<T>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Clone
+        + Default
+        + From<u8>
+        + std::iter::Sum<<T as Mul>::Output>,
+    Vec<T>: FromIterator<<T as Add>::Output>,
+    Vec<T>: FromIterator<<T as Sub>::Output>,
+    Vec<T>: FromIterator<<T as Mul>::Output>
+{
+    /// `dot`, but iterating i-k-j: each element of `self` is read once and
+    /// scaled into the whole output row at once, instead of re-cloning a
+    /// column of `rhs` for every output cell (cache-friendlier, no O(rows *
+    /// cols) column allocations).
+    pub fn dot_tiled(&self, rhs: &Self) -> Self {
+        if self.cols != rhs.rows {
+            panic!("{}", "Incompatible matrices dims for dot product.".red());
+        }
+
+        let mut res: Matrix<T> = Matrix::zeros(self.rows, rhs.cols);
+
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self.data[i][k].clone();
+                for j in 0..rhs.cols {
+                    res.data[i][j] = res.data[i][j].clone() + a.clone() * rhs.data[k][j].clone();
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Strassen's multiplication: splits both square operands into four
+    /// quadrants and recombines 7 sub-products (`m1..m7`, formed from
+    /// quadrant sums/differences) instead of the usual 8, recursing on
+    /// each half-sized sub-problem and falling back to [`Matrix::dot_tiled`]
+    /// once the operands are no longer square, evenly splittable, or larger
+    /// than [`STRASSEN_THRESHOLD`].
+    pub fn dot_strassen(&self, rhs: &Self) -> Self {
+        if self.cols != rhs.rows {
+            panic!("{}", "Incompatible matrices dims for dot product.".red());
+        }
+
+        if self.rows != self.cols
+            || rhs.rows != rhs.cols
+            || self.rows != rhs.rows
+            || self.rows < STRASSEN_THRESHOLD
+            || !self.rows.is_multiple_of(2)
+        {
+            return self.dot_tiled(rhs);
+        }
+
+        let half = self.rows / 2;
+        let (a11, a12, a21, a22) = self.split_quadrants(half);
+        let (b11, b12, b21, b22) = rhs.split_quadrants(half);
+
+        let m1 = (a11.clone() + a22.clone()).dot_strassen(&(b11.clone() + b22.clone()));
+        let m2 = (a21.clone() + a22.clone()).dot_strassen(&b11.clone());
+        let m3 = a11.clone().dot_strassen(&(b12.clone() - b22.clone()));
+        let m4 = a22.clone().dot_strassen(&(b21.clone() - b11.clone()));
+        let m5 = (a11.clone() + a12.clone()).dot_strassen(&b22.clone());
+        let m6 = (a21 - a11).dot_strassen(&(b11 + b12));
+        let m7 = (a12 - a22.clone()).dot_strassen(&(b21 + b22));
+
+        let c11 = m1.clone() + m4.clone() - m5.clone() + m7;
+        let c12 = m3.clone() + m5;
+        let c21 = m2.clone() + m4;
+        let c22 = m1 - m2 + m3 + m6;
+
+        Self::join_quadrants(c11, c12, c21, c22)
+    }
+
+    /// Entry point letting callers pick the multiplication algorithm.
+    pub fn dot_with(&self, rhs: &Self, strategy: DotStrategy) -> Self {
+        match strategy {
+            DotStrategy::Naive => self.dot(rhs),
+            DotStrategy::Tiled => self.dot_tiled(rhs),
+            DotStrategy::Strassen => self.dot_strassen(rhs),
+        }
+    }
+
+    // Split a square matrix into its four (rows/2 x cols/2) quadrants.
+    fn split_quadrants(&self, half: usize) -> (Self, Self, Self, Self) {
+        let mut top_left = vec![];
+        let mut top_right = vec![];
+        let mut bottom_left = vec![];
+        let mut bottom_right = vec![];
+
+        for row in &self.data[..half] {
+            top_left.push(row[..half].to_vec());
+            top_right.push(row[half..].to_vec());
+        }
+        for row in &self.data[half..] {
+            bottom_left.push(row[..half].to_vec());
+            bottom_right.push(row[half..].to_vec());
+        }
+
+        (
+            Matrix::new(top_left),
+            Matrix::new(top_right),
+            Matrix::new(bottom_left),
+            Matrix::new(bottom_right),
+        )
+    }
+
+    // Recombine the four quadrants produced by a Strassen step back into a
+    // single matrix.
+    fn join_quadrants(top_left: Self, top_right: Self, bottom_left: Self, bottom_right: Self) -> Self {
+        let mut data = vec![];
+
+        for i in 0..top_left.rows {
+            let mut row = top_left.data[i].clone();
+            row.extend(top_right.data[i].clone());
+            data.push(row);
+        }
+        for i in 0..bottom_left.rows {
+            let mut row = bottom_left.data[i].clone();
+            row.extend(bottom_right.data[i].clone());
+            data.push(row);
+        }
+
+        Matrix::new(data)
+    }
+}
+
+// `affine` broadcasts an extra bias add on top of `dot`, so it needs the
+// `Add` bound the plain integer API above does not.
+impl<T> Matrix<T>
+where
+    T: Add<Output = T>
+        + Clone
+        + Default
+        + From<u8>
+        + Mul
+        + std::iter::Sum<<T as Mul>::Output>,
+    Vec<T>: FromIterator<<T as Mul>::Output>
+{
+    /// `self.dot(weights)` with `bias` added to every row, i.e. `x*W + b`.
+    /// This is the affine half of a feed-forward/LSTM layer; chain with
+    /// [`Matrix::map`] for the nonlinearity, e.g.
+    /// `m.affine(&w1, &b1).map(activations::f64::sigmoid)`.
+    pub fn affine(&self, weights: &Matrix<T>, bias: &[T]) -> Matrix<T> {
+        if bias.len() != weights.cols {
+            panic!("{}", "Bias length must match weights column count.".red());
+        }
+
+        let mut res = self.dot(weights);
+
+        for row in res.data.iter_mut() {
+            for (v, b) in row.iter_mut().zip(bias.iter()) {
+                *v = v.clone() + b.clone();
+            }
+        }
+
+        res
+    }
+}
+
+// Gauss-Jordan elimination requires dividing by the pivot, so this is kept
+// in its own impl block gated on `Div`/`PartialOrd` bounds the integer API
+// above does not need.
+impl<T> Matrix<T>
+where
+    T: Clone
+        + Default
+        + From<u8>
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + std::ops::Div<Output = T>
+        + PartialOrd,
+{
+    // Magnitude of `v`, used to pick the largest-magnitude pivot.
+    fn abs(v: T) -> T {
+        if v < T::from(0u8) {
+            T::from(0u8) - v
+        } else {
+            v
+        }
+    }
+
+    /// Reduced row echelon form, obtained via Gauss-Jordan elimination with
+    /// partial pivoting (largest-magnitude pivot in each column is swapped
+    /// to the top before elimination).
+    pub fn rref(&self) -> Matrix<T> {
+        let mut m = self.clone();
+        let mut pivot_row = 0;
+
+        for col in 0..m.cols {
+            if pivot_row >= m.rows {
+                break;
+            }
+
+            let mut max_row = pivot_row;
+            let mut max_val = Self::abs(m.data[pivot_row][col].clone());
+            for r in (pivot_row + 1)..m.rows {
+                let val = Self::abs(m.data[r][col].clone());
+                if val > max_val {
+                    max_val = val;
+                    max_row = r;
+                }
+            }
+
+            if max_val == T::from(0u8) {
+                continue;
+            }
+
+            m.data.swap(pivot_row, max_row);
+
+            let pivot = m.data[pivot_row][col].clone();
+            for v in m.data[pivot_row].iter_mut() {
+                *v = v.clone() / pivot.clone();
+            }
+
+            for r in 0..m.rows {
+                if r == pivot_row {
+                    continue;
+                }
+
+                let factor = m.data[r][col].clone();
+                if factor == T::from(0u8) {
+                    continue;
+                }
+
+                for c in 0..m.cols {
+                    let sub = factor.clone() * m.data[pivot_row][c].clone();
+                    m.data[r][c] = m.data[r][c].clone() - sub;
+                }
+            }
+
+            pivot_row += 1;
+        }
+
+        m
+    }
+
+    /// Number of linearly independent rows, i.e. the number of non-zero
+    /// rows once in reduced row echelon form.
+    pub fn rank(&self) -> usize {
+        self.rref()
+            .data
+            .iter()
+            .filter(|row| row.iter().any(|v| *v != T::from(0u8)))
+            .count()
+    }
+
+    /// Determinant, computed as the product of the pivots found during
+    /// Gaussian elimination (with partial pivoting), times the sign picked
+    /// up from row swaps. Panics if `self` is not square.
+    pub fn determinant(&self) -> T {
+        if self.rows != self.cols {
+            panic!("{}", "Determinant requires a square matrix.".red());
+        }
+
+        let mut m = self.clone();
+        let n = m.rows;
+        let mut det = T::from(1u8);
+        let mut sign = T::from(1u8);
+
+        for col in 0..n {
+            let mut max_row = col;
+            let mut max_val = Self::abs(m.data[col][col].clone());
+            for r in (col + 1)..n {
+                let val = Self::abs(m.data[r][col].clone());
+                if val > max_val {
+                    max_val = val;
+                    max_row = r;
+                }
+            }
+
+            if max_val == T::from(0u8) {
+                return T::from(0u8);
+            }
+
+            if max_row != col {
+                m.data.swap(col, max_row);
+                sign = T::from(0u8) - sign;
+            }
+
+            let pivot = m.data[col][col].clone();
+            det = det * pivot.clone();
+
+            for r in (col + 1)..n {
+                let factor = m.data[r][col].clone() / pivot.clone();
+                for c in col..n {
+                    let sub = factor.clone() * m.data[col][c].clone();
+                    m.data[r][c] = m.data[r][c].clone() - sub;
+                }
+            }
+        }
+
+        det * sign
+    }
+
+    /// Matrix inverse, obtained by reducing `[self | I]` and reading off the
+    /// right half. Returns `None` when `self` is singular (a zero pivot
+    /// column is found). Panics if `self` is not square.
+    pub fn inverse(&self) -> Option<Matrix<T>> {
+        if self.rows != self.cols {
+            panic!("{}", "Inverse requires a square matrix.".red());
+        }
+
+        let n = self.rows;
+        let mut data: Vec<Vec<T>> = Vec::with_capacity(n);
+        for (i, row) in self.data.iter().enumerate() {
+            let mut row = row.clone();
+            for j in 0..n {
+                row.push(if i == j { T::from(1u8) } else { T::from(0u8) });
+            }
+            data.push(row);
+        }
+        let mut m = Matrix { rows: n, cols: 2 * n, data };
+
+        let mut pivot_row = 0;
+        for col in 0..n {
+            let mut max_row = pivot_row;
+            let mut max_val = Self::abs(m.data[pivot_row][col].clone());
+            for r in (pivot_row + 1)..n {
+                let val = Self::abs(m.data[r][col].clone());
+                if val > max_val {
+                    max_val = val;
+                    max_row = r;
+                }
+            }
+
+            if max_val == T::from(0u8) {
+                return None;
+            }
+
+            m.data.swap(pivot_row, max_row);
+
+            let pivot = m.data[pivot_row][col].clone();
+            for v in m.data[pivot_row].iter_mut() {
+                *v = v.clone() / pivot.clone();
+            }
+
+            for r in 0..n {
+                if r == pivot_row {
+                    continue;
+                }
+
+                let factor = m.data[r][col].clone();
+                if factor == T::from(0u8) {
+                    continue;
+                }
+
+                for c in 0..m.cols {
+                    let sub = factor.clone() * m.data[pivot_row][c].clone();
+                    m.data[r][c] = m.data[r][c].clone() - sub;
+                }
+            }
+
+            pivot_row += 1;
+        }
+
+        let data = m.data.into_iter().map(|row| row[n..].to_vec()).collect();
+        Some(Matrix { rows: n, cols: n, data })
+    }
+}
+
 impl<T: std::fmt::Debug> fmt::Display for Matrix<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut output: Vec<String> = vec![];
@@ -173,3 +771,72 @@ impl<T: std::fmt::Debug> fmt::Display for Matrix<T> {
         write!(f, "{}", output.join("\n"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn inverse_times_self_is_identity() {
+        let a: Matrix<f64> = Matrix::new(vec![
+            vec![2.0, 1.0, 1.0],
+            vec![1.0, 3.0, 2.0],
+            vec![1.0, 0.0, 0.0],
+        ]);
+
+        let inv = a.inverse().expect("matrix should be invertible");
+        let product = a.dot(&inv);
+        let identity = Matrix::identity(3);
+
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!(approx_eq(product.data[i][j], identity.data[i][j]));
+            }
+        }
+    }
+
+    #[test]
+    fn determinant_matches_known_value() {
+        let a: Matrix<f64> = Matrix::new(vec![
+            vec![2.0, 1.0, 1.0],
+            vec![1.0, 3.0, 2.0],
+            vec![1.0, 0.0, 0.0],
+        ]);
+
+        assert!(approx_eq(a.determinant(), -1.0));
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        let a: Matrix<f64> = Matrix::new(vec![vec![1.0, 2.0], vec![2.0, 4.0]]);
+
+        assert!(a.inverse().is_none());
+    }
+
+    #[test]
+    fn strassen_matches_naive_dot_on_non_power_of_two_size() {
+        let n = 6;
+        let a: Matrix<i64> = Matrix::new((0..n).map(|i| (0..n).map(|j| (i * n + j) as i64).collect()).collect());
+        let b: Matrix<i64> = Matrix::new((0..n).map(|i| (0..n).map(|j| (i + j) as i64).collect()).collect());
+
+        assert_eq!(a.dot(&b).data, a.dot_strassen(&b).data);
+        assert_eq!(a.dot(&b).data, a.dot_tiled(&b).data);
+        assert_eq!(a.dot(&b).data, a.dot_with(&b, DotStrategy::Strassen).data);
+    }
+
+    #[test]
+    fn pow_matches_repeated_dot() {
+        let a: Matrix<i64> = Matrix::new(vec![vec![1, 1], vec![1, 0]]);
+
+        let mut expected = Matrix::identity(2);
+        for _ in 0..7 {
+            expected = expected.dot(&a);
+        }
+
+        assert_eq!(a.pow(7).data, expected.data);
+    }
+}
@@ -0,0 +1,69 @@
+use std::iter::Sum;
+use std::ops::{Add, Mul, Sub};
+
+/// An integer reduced modulo the const generic `M`, usable as a `Matrix<T>`
+/// element type.
+///
+/// This is the element type to reach for when a recurrence (counting walks,
+/// population spread, ...) needs to be evaluated under a modulus instead of
+/// plain primitive integers, e.g. `ModInt<998244353>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    pub fn new(value: u64) -> Self {
+        ModInt(value % M)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    // Reduce a `u128` intermediate, so `Add`/`Sub`/`Mul` stay correct even
+    // when `M` is close to `u64::MAX` and `self.0 + rhs.0` would overflow.
+    fn reduce(value: u128) -> Self {
+        ModInt((value % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Default for ModInt<M> {
+    fn default() -> Self {
+        ModInt(0)
+    }
+}
+
+impl<const M: u64> From<u8> for ModInt<M> {
+    fn from(value: u8) -> Self {
+        ModInt::new(value as u64)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::reduce(self.0 as u128 + rhs.0 as u128)
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::reduce(M as u128 + self.0 as u128 - rhs.0 as u128)
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::reduce(self.0 as u128 * rhs.0 as u128)
+    }
+}
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt::new(0), |acc, x| acc + x)
+    }
+}